@@ -0,0 +1,72 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2018-2021 Andre Richter <andre.o.richter@gmail.com>
+
+//! Architectural translation-table enable-path additions for the two-regime, higher-half kernel
+//! layout.
+//!
+//! This configures the `TTBR1_EL1` half of `TCR_EL1` (including `T1SZ`) and loads the kernel's
+//! tables into `TTBR1_EL1`. `T0SZ` and `TTBR0_EL1` are not written; walks through `TTBR0_EL1` are
+//! disabled with `EPD0`, which leaves that regime free for a user address space.
+
+use crate::bsp;
+use cortex_a::{asm::barrier, registers::TCR_EL1};
+use tock_registers::interfaces::{ReadWriteable, Writeable};
+
+//--------------------------------------------------------------------------------------------------
+// Private Code
+//--------------------------------------------------------------------------------------------------
+
+/// The `T1SZ` value that makes `TTBR1_EL1` cover exactly `addr_space_size` bytes.
+const fn t1sz_for_addr_space_size(addr_space_size: usize) -> u64 {
+    64 - addr_space_size.trailing_zeros() as u64
+}
+
+//--------------------------------------------------------------------------------------------------
+// Public Code
+//--------------------------------------------------------------------------------------------------
+
+/// Program the `TTBR1_EL1` regime of `TCR_EL1` and `TTBR1_EL1` itself as part of enabling the MMU.
+///
+/// `phys_kernel_tables_base_addr` is the physical address of `KERNEL_TABLES`, loaded into
+/// `TTBR1_EL1`. `T0SZ` and `TTBR0_EL1` are left at their previous values; `EPD0` disables
+/// translation walks through `TTBR0_EL1`.
+///
+/// # Safety
+///
+/// - Must be called only as part of the MMU enable sequence, before the MMU is switched on, and
+///   only from the boot core.
+pub unsafe fn configure_translation_control(phys_kernel_tables_base_addr: u64) {
+    let t1sz = t1sz_for_addr_space_size(bsp::memory::mmu::KernelVirtAddrSpace::SIZE);
+
+    TCR_EL1.modify(
+        TCR_EL1::TG1::KiB_64
+            + TCR_EL1::SH1::Inner
+            + TCR_EL1::ORGN1::WriteBack_ReadAlloc_WriteAlloc_Cacheable
+            + TCR_EL1::IRGN1::WriteBack_ReadAlloc_WriteAlloc_Cacheable
+            + TCR_EL1::EPD1::EnableTTBR1Walks
+            + TCR_EL1::T1SZ.val(t1sz)
+            + TCR_EL1::EPD0::DisableTTBR0Walks,
+    );
+
+    cortex_a::registers::TTBR1_EL1.set(phys_kernel_tables_base_addr);
+
+    barrier::isb(barrier::SY);
+}
+
+//--------------------------------------------------------------------------------------------------
+// Testing
+//--------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_macros::kernel_test;
+
+    /// `KernelVirtAddrSpace` is 8 GiB (2^33 bytes), which must produce a `T1SZ` of `64 - 33 == 31`
+    /// for `TTBR1_EL1` to cover exactly that range.
+    #[kernel_test]
+    fn t1sz_matches_the_kernels_virtual_address_space_size() {
+        assert_eq!(t1sz_for_addr_space_size(8 * 1024 * 1024 * 1024), 31);
+    }
+}
@@ -10,12 +10,13 @@ use crate::{
         mmu as generic_mmu,
         mmu::{
             AccessPermissions, AddressSpace, AssociatedTranslationTable, AttributeFields,
-            MemAttributes, Page, PageSliceDescriptor, TranslationGranule,
+            MemAttributes, PageSliceDescriptor, TranslationGranule,
         },
-        Physical, Virtual,
+        Address, Physical, Virtual,
     },
     synchronization::InitStateLock,
 };
+use core::marker::PhantomData;
 
 //--------------------------------------------------------------------------------------------------
 // Private Definitions
@@ -24,6 +25,106 @@ use crate::{
 type KernelTranslationTable =
     <KernelVirtAddrSpace as AssociatedTranslationTable>::TableStartFromBottom;
 
+/// Size of the region reserved for MMIO remapping, carved out of the top of
+/// `KernelVirtAddrSpace`.
+const MMIO_REMAP_SIZE: usize = 8 * 1024 * 1024;
+
+/// Unmapped guard space left at the very top of the 64-bit address space, i.e. the topmost bytes
+/// of `KernelVirtAddrSpace`, above the MMIO remap region.
+///
+/// `virt_kernel_base() + KernelVirtAddrSpace::SIZE` equals `usize::MAX + 1`, so without this the
+/// MMIO remap region would end there, and computing the (exclusive) end address of the topmost
+/// MMIO allocation would overflow `usize`. Reserving one granule of headroom here keeps every
+/// exclusive-end computation in this file representable.
+const TOP_GUARD_SIZE: usize = KernelGranule::SIZE;
+
+/// A `usize`-backed page address, generic over the address kind (`Physical` or `Virtual`).
+///
+/// `phys_addr_space_end_page()` used to return a raw `*const Page<Physical>`, which isn't `Send`.
+/// `MMIOVaAllocator` below never actually held one of those pointers, so it didn't need this; the
+/// point is the raw pointer itself, not any current caller of it. This type replaces it with a
+/// plain value so that whatever ends up tracking physical address space state next (e.g. a frame
+/// allocator built the same way as `MMIOVaAllocator`) can store it behind a synchronization
+/// primitive like `InitStateLock` without reaching for an unsafe `Send` impl.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+struct PageAddress<ATYPE> {
+    inner: usize,
+    _address_type: PhantomData<fn() -> ATYPE>,
+}
+
+impl<ATYPE> PageAddress<ATYPE> {
+    /// Create an instance.
+    fn new(addr: usize) -> Self {
+        debug_assert_eq!(
+            addr % KernelGranule::SIZE,
+            0,
+            "Address is not granule-aligned"
+        );
+
+        Self {
+            inner: addr,
+            _address_type: PhantomData,
+        }
+    }
+
+    /// Offset `self` by `count` pages, returning `None` on over- or underflow.
+    fn checked_offset(self, count: isize) -> Option<Self> {
+        let offset = count.checked_mul(KernelGranule::SIZE as isize)?;
+
+        let addr = if offset >= 0 {
+            self.inner.checked_add(offset as usize)?
+        } else {
+            self.inner.checked_sub(offset.unsigned_abs())?
+        };
+
+        Some(Self::new(addr))
+    }
+
+    /// Consume `self` and return the raw `usize` value.
+    const fn into_inner(self) -> usize {
+        self.inner
+    }
+}
+
+/// A top-down bump allocator that hands out virtual page ranges from the reserved MMIO remap
+/// region.
+struct MMIOVaAllocator {
+    /// Number of pages not yet handed out. Pages are handed out top-down, so every page at or
+    /// above this index (counted from the start of the reserved region) is already allocated.
+    next_free_page: usize,
+}
+
+impl MMIOVaAllocator {
+    /// Create an instance.
+    const fn new() -> Self {
+        Self {
+            next_free_page: size_to_num_pages(MMIO_REMAP_SIZE),
+        }
+    }
+
+    /// Allocate a virtually contiguous slice of `num_pages` pages from the reserved MMIO remap
+    /// region.
+    fn alloc(&mut self, num_pages: usize) -> Result<PageSliceDescriptor<Virtual>, &'static str> {
+        if num_pages == 0 {
+            return Err("Requested zero pages");
+        }
+
+        if num_pages > self.next_free_page {
+            return Err("Not enough MMIO virtual address space remaining");
+        }
+
+        self.next_free_page -= num_pages;
+
+        let start =
+            mmio_remap_region_start().into_usize() + (self.next_free_page << KernelGranule::SHIFT);
+
+        Ok(PageSliceDescriptor::from_addr(
+            Address::new(start),
+            num_pages,
+        ))
+    }
+}
+
 //--------------------------------------------------------------------------------------------------
 // Public Definitions
 //--------------------------------------------------------------------------------------------------
@@ -45,9 +146,21 @@ pub type KernelVirtAddrSpace = AddressSpace<{ 8 * 1024 * 1024 * 1024 }>;
 ///
 /// That is, `size_of(InitStateLock<KernelTranslationTable>) == size_of(KernelTranslationTable)`.
 /// There is a unit tests that checks this porperty.
+///
+/// With the `build_time_tables` feature, this is placed into the `.kernel_tables` section, which
+/// is exactly the section `tools/kernel_tables_gen` patches the precomputed entries into. Without
+/// it, `KERNEL_TABLES` is populated at runtime by `kernel_map_binary()` and lives in `.bss` as
+/// usual.
+#[cfg_attr(feature = "build_time_tables", link_section = ".kernel_tables")]
 static KERNEL_TABLES: InitStateLock<KernelTranslationTable> =
     InitStateLock::new(KernelTranslationTable::new());
 
+/// The allocator for the reserved MMIO remap region.
+///
+/// It is mandatory that InitStateLock is transparent, just like for `KERNEL_TABLES` above.
+static MMIO_VA_ALLOCATOR: InitStateLock<MMIOVaAllocator> =
+    InitStateLock::new(MMIOVaAllocator::new());
+
 //--------------------------------------------------------------------------------------------------
 // Private Code
 //--------------------------------------------------------------------------------------------------
@@ -60,42 +173,101 @@ const fn size_to_num_pages(size: usize) -> usize {
     size >> KernelGranule::SHIFT
 }
 
+/// Offset a link-time address by `virt_kernel_base()`, turning it into the kernel's actual
+/// runtime virtual address under the higher-half layout.
+fn relocate(addr: Address<Virtual>) -> Address<Virtual> {
+    Address::new(virt_kernel_base().into_usize() + addr.into_usize())
+}
+
 /// The boot core's stack.
 fn virt_stack_page_desc() -> PageSliceDescriptor<Virtual> {
     let num_pages = size_to_num_pages(super::boot_core_stack_size());
 
-    PageSliceDescriptor::from_addr(super::virt_boot_core_stack_start(), num_pages)
+    PageSliceDescriptor::from_addr(relocate(super::virt_boot_core_stack_start()), num_pages)
 }
 
 /// The Read-Only (RO) pages of the kernel binary.
 fn virt_ro_page_desc() -> PageSliceDescriptor<Virtual> {
     let num_pages = size_to_num_pages(super::ro_size());
 
-    PageSliceDescriptor::from_addr(super::virt_ro_start(), num_pages)
+    PageSliceDescriptor::from_addr(relocate(super::virt_ro_start()), num_pages)
 }
 
 /// The data pages of the kernel binary.
 fn virt_data_page_desc() -> PageSliceDescriptor<Virtual> {
     let num_pages = size_to_num_pages(super::data_size());
 
-    PageSliceDescriptor::from_addr(super::virt_data_start(), num_pages)
+    PageSliceDescriptor::from_addr(relocate(super::virt_data_start()), num_pages)
 }
 
-// The binary is still identity mapped, so we don't need to convert in the following.
+// The kernel now lives in the high half of the address space, reached through `TTBR1_EL1`, while
+// its link-time addresses are still the low, physical ones `TTBR0_EL1` would use. So we do need a
+// real VA -> PA translation here, not just a type-level `.into()`.
+
+/// Translate a descriptor expressed in the kernel's runtime virtual address space back down to
+/// the physical address space it is backed by.
+fn to_phys(desc: PageSliceDescriptor<Virtual>) -> PageSliceDescriptor<Physical> {
+    let num_pages =
+        (desc.end_addr().into_usize() - desc.start_addr().into_usize()) >> KernelGranule::SHIFT;
+    let start = desc.start_addr().into_usize() - virt_kernel_base().into_usize();
+
+    PageSliceDescriptor::from_addr(Address::new(start), num_pages)
+}
 
 /// The boot core's stack.
 fn phys_stack_page_desc() -> PageSliceDescriptor<Physical> {
-    virt_stack_page_desc().into()
+    to_phys(virt_stack_page_desc())
 }
 
 /// The Read-Only (RO) pages of the kernel binary.
 fn phys_ro_page_desc() -> PageSliceDescriptor<Physical> {
-    virt_ro_page_desc().into()
+    to_phys(virt_ro_page_desc())
 }
 
 /// The data pages of the kernel binary.
 fn phys_data_page_desc() -> PageSliceDescriptor<Physical> {
-    virt_data_page_desc().into()
+    to_phys(virt_data_page_desc())
+}
+
+/// Start address of the region reserved for MMIO remapping, i.e. the `MMIO_REMAP_SIZE` bytes of
+/// `KernelVirtAddrSpace` just below the `TOP_GUARD_SIZE` guard at its top.
+fn mmio_remap_region_start() -> Address<Virtual> {
+    Address::new(
+        virt_kernel_base().into_usize()
+            + (KernelVirtAddrSpace::SIZE - TOP_GUARD_SIZE - MMIO_REMAP_SIZE),
+    )
+}
+
+/// The kernel's virtual base address.
+///
+/// Under the higher-half layout, `KernelVirtAddrSpace` occupies the very top of the 64-bit address
+/// space and is reached through `TTBR1_EL1`, leaving `TTBR0_EL1` free for a user address space.
+/// The base is the lowest address of the range `TTBR1_EL1` covers with the `T1SZ` derived from
+/// `KernelVirtAddrSpace::SIZE`, so every address in the space has the all-ones prefix `TTBR1_EL1`
+/// expects.
+fn virt_kernel_base() -> Address<Virtual> {
+    Address::new(usize::MAX - (KernelVirtAddrSpace::SIZE - 1))
+}
+
+/// Pack one page's physical output address and access/execute-never flags into a descriptor,
+/// matching the encoding `tools/kernel_tables_gen::pack_descriptor` uses to precompute
+/// `KERNEL_TABLES`' entries: bit 0 is `ReadWrite`, bit 1 is `execute_never`, and the
+/// granule-aligned high bits are the physical output address.
+///
+/// Kept here, alongside a test that exercises it against the kernel's own binary segments, so the
+/// two independently hand-written encodings (this one and the host tool's) have something to be
+/// checked against instead of silently drifting apart.
+fn encode_precomputed_page_descriptor(phys_page_addr: usize, attributes: &AttributeFields) -> u64 {
+    let mut descriptor = (phys_page_addr as u64) & !(KernelGranule::SIZE as u64 - 1);
+
+    if matches!(attributes.acc_perms, AccessPermissions::ReadWrite) {
+        descriptor |= 0b01;
+    }
+    if attributes.execute_never {
+        descriptor |= 0b10;
+    }
+
+    descriptor
 }
 
 //--------------------------------------------------------------------------------------------------
@@ -107,19 +279,48 @@ pub fn kernel_translation_tables() -> &'static InitStateLock<KernelTranslationTa
     &KERNEL_TABLES
 }
 
-/// Pointer to the last page of the physical address space.
-pub fn phys_addr_space_end_page() -> *const Page<Physical> {
-    common::align_down(
+/// Address of the last page of the physical address space.
+pub fn phys_addr_space_end_page() -> PageAddress<Physical> {
+    PageAddress::new(common::align_down(
         super::phys_addr_space_end().into_usize(),
         KernelGranule::SIZE,
-    ) as *const Page<_>
+    ))
 }
 
-/// Map the kernel binary.
+/// The address `KERNEL_TABLES` is to be loaded into `TTBR1_EL1` at, as handed to
+/// `configure_translation_registers()`.
+///
+/// Split out from `configure_translation_registers()` so the address it is about to program into
+/// hardware can be checked by a test without actually touching `TCR_EL1`/`TTBR1_EL1`.
+fn kernel_tables_phys_base_addr() -> u64 {
+    &KERNEL_TABLES as *const _ as u64
+}
+
+/// Program the two-regime split and both TTBR registers so the high-half addresses produced by
+/// `virt_kernel_base()` are actually reachable via `TTBR1_EL1` once the MMU is enabled.
+///
+/// Called from `kernel_map_binary()` once `KERNEL_TABLES` holds the mappings it is about to
+/// point `TTBR1_EL1` at.
+///
+/// Must be called as part of the MMU enable sequence, before the MMU is switched on.
+///
+/// # Safety
+///
+/// - See `crate::_arch::aarch64::memory::mmu::configure_translation_control`.
+unsafe fn configure_translation_registers() {
+    crate::_arch::aarch64::memory::mmu::configure_translation_control(
+        kernel_tables_phys_base_addr(),
+    );
+}
+
+/// Map the kernel binary, then program the translation control registers so the mappings just
+/// installed in `KERNEL_TABLES` are reachable via `TTBR1_EL1` once the MMU is enabled.
 ///
 /// # Safety
 ///
 /// - Any miscalculation or attribute error will likely be fatal. Needs careful manual checking.
+/// - Must be called before the MMU is switched on, and only from the boot core.
+#[cfg(not(feature = "build_time_tables"))]
 pub unsafe fn kernel_map_binary() -> Result<(), &'static str> {
     generic_mmu::kernel_map_pages_at(
         "Kernel boot-core stack",
@@ -154,9 +355,118 @@ pub unsafe fn kernel_map_binary() -> Result<(), &'static str> {
         },
     )?;
 
+    configure_translation_registers();
+
+    Ok(())
+}
+
+/// Map the kernel binary.
+///
+/// With the `build_time_tables` feature, the host-side `kernel_tables_gen` tool (see `tools/`)
+/// has already precomputed and embedded `KERNEL_TABLES`' entries for the stack/RO/data segments
+/// into the kernel image via the `.kernel_tables` section (see `KERNEL_TABLES`'s doc comment).
+/// This function does not repopulate them.
+///
+/// With `debug_assertions` on, it still re-derives the expected descriptors and compares them
+/// against what was actually read out of `kernel_translation_tables()`, so a broken precompute
+/// step fails loudly here instead of silently booting with bad mappings; that check costs the
+/// same descriptor computation as the runtime-populate path. With `debug_assertions` off, none of
+/// that runs and populating the tables really is the no-op the precomputation is meant to buy.
+/// Programming the translation control registers below is not part of that trade-off, though:
+/// `TCR_EL1`/`TTBR1_EL1` are reset by the hardware every boot, precomputed tables or not, so this
+/// function always does that regardless of `debug_assertions`.
+///
+/// # Safety
+///
+/// - Any miscalculation or attribute error will likely be fatal. Needs careful manual checking.
+/// - Must be called before the MMU is switched on, and only from the boot core.
+#[cfg(feature = "build_time_tables")]
+pub unsafe fn kernel_map_binary() -> Result<(), &'static str> {
+    #[cfg(debug_assertions)]
+    {
+        generic_mmu::kernel_verify_precomputed_mapping(
+            kernel_translation_tables(),
+            "Kernel boot-core stack",
+            &virt_stack_page_desc(),
+            &phys_stack_page_desc(),
+            &AttributeFields {
+                mem_attributes: MemAttributes::CacheableDRAM,
+                acc_perms: AccessPermissions::ReadWrite,
+                execute_never: true,
+            },
+        )?;
+
+        generic_mmu::kernel_verify_precomputed_mapping(
+            kernel_translation_tables(),
+            "Kernel code and RO data",
+            &virt_ro_page_desc(),
+            &phys_ro_page_desc(),
+            &AttributeFields {
+                mem_attributes: MemAttributes::CacheableDRAM,
+                acc_perms: AccessPermissions::ReadOnly,
+                execute_never: false,
+            },
+        )?;
+
+        generic_mmu::kernel_verify_precomputed_mapping(
+            kernel_translation_tables(),
+            "Kernel data and bss",
+            &virt_data_page_desc(),
+            &phys_data_page_desc(),
+            &AttributeFields {
+                mem_attributes: MemAttributes::CacheableDRAM,
+                acc_perms: AccessPermissions::ReadWrite,
+                execute_never: true,
+            },
+        )?;
+    }
+
+    configure_translation_registers();
+
     Ok(())
 }
 
+/// Map a region of MMIO-capable physical memory into the reserved MMIO remap region of the
+/// kernel's virtual address space.
+///
+/// # Safety
+///
+/// - The caller must ensure that `phys_mmio_descriptor` actually describes a valid MMIO region.
+pub unsafe fn kernel_map_mmio(
+    name: &'static str,
+    phys_mmio_descriptor: &PageSliceDescriptor<Physical>,
+) -> Result<Address<Virtual>, &'static str> {
+    let phys_start_addr = phys_mmio_descriptor.start_addr();
+    let offset_into_start_page = phys_start_addr.into_usize() % KernelGranule::SIZE;
+
+    let phys_region_start_addr =
+        common::align_down(phys_start_addr.into_usize(), KernelGranule::SIZE);
+    let phys_region_size = phys_mmio_descriptor.end_addr().into_usize() - phys_region_start_addr;
+    let num_pages = size_to_num_pages(common::align_down(
+        phys_region_size + KernelGranule::SIZE - 1,
+        KernelGranule::SIZE,
+    ));
+    let phys_mmio_desc =
+        PageSliceDescriptor::from_addr(Address::new(phys_region_start_addr), num_pages);
+
+    let virt_mmio_desc = MMIO_VA_ALLOCATOR.write(|allocator| allocator.alloc(num_pages))?;
+
+    generic_mmu::kernel_map_pages_at(
+        name,
+        &virt_mmio_desc,
+        &phys_mmio_desc,
+        &AttributeFields {
+            mem_attributes: MemAttributes::Device,
+            acc_perms: AccessPermissions::ReadWrite,
+            execute_never: true,
+        },
+    )?;
+
+    Ok(Address::new(
+        virt_mmio_desc.start_addr().into_usize() + offset_into_start_page,
+    ))
+}
+
 //--------------------------------------------------------------------------------------------------
 // Testing
 //--------------------------------------------------------------------------------------------------
@@ -179,6 +489,16 @@ mod tests {
         }
     }
 
+    /// Ensure the kernel's virtual memory layout now lives in the high half of the address space.
+    #[kernel_test]
+    fn virt_mem_layout_is_in_the_high_range() {
+        let kernel_base = virt_kernel_base().into_usize();
+
+        for i in [virt_stack_page_desc, virt_ro_page_desc, virt_data_page_desc].iter() {
+            assert!(i().start_addr().into_usize() >= kernel_base);
+        }
+    }
+
     /// Ensure the kernel's virtual memory layout is free of overlaps.
     #[kernel_test]
     fn virt_mem_layout_has_no_overlaps() {
@@ -199,6 +519,10 @@ mod tests {
     }
 
     /// Check if KERNEL_TABLES is in .bss.
+    ///
+    /// With `build_time_tables`, KERNEL_TABLES is placed in `.kernel_tables` instead (see its
+    /// doc comment), so this invariant no longer applies.
+    #[cfg(not(feature = "build_time_tables"))]
     #[kernel_test]
     fn kernel_tables_in_bss() {
         let bss_range = super::super::bss_range_inclusive();
@@ -206,4 +530,159 @@ mod tests {
 
         assert!(bss_range.contains(&kernel_tables_addr));
     }
+
+    /// Ensure `configure_translation_registers()`'s call site actually has a real `KERNEL_TABLES`
+    /// address to hand to `TTBR1_EL1`, i.e. `kernel_tables_phys_base_addr()` does not just return
+    /// `0` or some other placeholder.
+    #[kernel_test]
+    fn kernel_tables_phys_base_addr_points_at_kernel_tables() {
+        assert_eq!(
+            kernel_tables_phys_base_addr(),
+            &KERNEL_TABLES as *const _ as u64
+        );
+        assert_ne!(kernel_tables_phys_base_addr(), 0);
+    }
+
+    /// Ensure MMIO remap allocations stay within the reserved region and never overlap the
+    /// binary's own descriptors.
+    #[kernel_test]
+    fn mmio_remap_allocations_stay_within_reserved_region() {
+        let region_start = mmio_remap_region_start().into_usize();
+        let region_end = region_start + MMIO_REMAP_SIZE;
+
+        let mut allocator = MMIOVaAllocator::new();
+        let allocations = [
+            allocator.alloc(1).unwrap(),
+            allocator.alloc(4).unwrap(),
+            allocator.alloc(16).unwrap(),
+        ];
+
+        let binary_layout = [
+            virt_stack_page_desc(),
+            virt_ro_page_desc(),
+            virt_data_page_desc(),
+        ];
+
+        for (i, first) in allocations.iter().enumerate() {
+            let start = first.start_addr().into_usize();
+            let end = first.end_addr().into_usize();
+
+            assert!(start >= region_start);
+            assert!(end <= region_end);
+
+            for second in allocations.iter().skip(i + 1) {
+                assert!(!first.contains(second.start_addr()));
+                assert!(!first.contains(second.end_addr_inclusive()));
+            }
+
+            for binary_desc in binary_layout.iter() {
+                assert!(!binary_desc.contains(first.start_addr()));
+                assert!(!binary_desc.contains(first.end_addr_inclusive()));
+            }
+        }
+    }
+
+    /// Ensure the allocator refuses requests that exceed the reserved region.
+    #[kernel_test]
+    fn mmio_remap_allocator_rejects_oversized_request() {
+        let mut allocator = MMIOVaAllocator::new();
+        let too_many_pages = size_to_num_pages(MMIO_REMAP_SIZE) + 1;
+
+        assert!(allocator.alloc(too_many_pages).is_err());
+    }
+
+    /// The very first `alloc(1)` call hands out the topmost page of the reserved region, i.e. the
+    /// common single-page MMIO mapping case. Its exclusive end address must not require computing
+    /// past `usize::MAX`, which is exactly what `TOP_GUARD_SIZE` guards against.
+    #[kernel_test]
+    fn first_single_page_allocation_does_not_overflow_its_end_address() {
+        let mut allocator = MMIOVaAllocator::new();
+        let desc = allocator.alloc(1).unwrap();
+
+        assert_eq!(
+            desc.start_addr().into_usize(),
+            usize::MAX - TOP_GUARD_SIZE - KernelGranule::SIZE + 1
+        );
+        assert_eq!(
+            desc.end_addr().into_usize(),
+            usize::MAX - TOP_GUARD_SIZE + 1
+        );
+    }
+
+    /// Ensure `encode_precomputed_page_descriptor` agrees, page by page, with the `AttributeFields`
+    /// `kernel_map_binary()` assigns to the kernel's own binary segments at runtime. This is the
+    /// same per-page derivation `tools/kernel_tables_gen` performs from the ELF program headers. A
+    /// failure here means this encoding no longer follows the layout documented on
+    /// `encode_precomputed_page_descriptor`; the host tool's `pack_descriptor` is not exercised
+    /// here and is tested against that layout by the tool's own tests.
+    #[kernel_test]
+    fn precomputed_descriptors_match_runtime_attribute_fields() {
+        let segments = [
+            (
+                phys_stack_page_desc(),
+                AttributeFields {
+                    mem_attributes: MemAttributes::CacheableDRAM,
+                    acc_perms: AccessPermissions::ReadWrite,
+                    execute_never: true,
+                },
+            ),
+            (
+                phys_ro_page_desc(),
+                AttributeFields {
+                    mem_attributes: MemAttributes::CacheableDRAM,
+                    acc_perms: AccessPermissions::ReadOnly,
+                    execute_never: false,
+                },
+            ),
+            (
+                phys_data_page_desc(),
+                AttributeFields {
+                    mem_attributes: MemAttributes::CacheableDRAM,
+                    acc_perms: AccessPermissions::ReadWrite,
+                    execute_never: true,
+                },
+            ),
+        ];
+
+        for (phys, attributes) in segments.iter() {
+            let phys_start = phys.start_addr().into_usize();
+            let num_pages = (phys.end_addr().into_usize() - phys_start) >> KernelGranule::SHIFT;
+
+            for i in 0..num_pages {
+                let phys_page_addr = phys_start + (i << KernelGranule::SHIFT);
+                let descriptor = encode_precomputed_page_descriptor(phys_page_addr, attributes);
+
+                assert_eq!(
+                    descriptor & !(KernelGranule::SIZE as u64 - 1),
+                    phys_page_addr as u64
+                );
+                assert_eq!(
+                    descriptor & 0b01 != 0,
+                    matches!(attributes.acc_perms, AccessPermissions::ReadWrite)
+                );
+                assert_eq!(descriptor & 0b10 != 0, attributes.execute_never);
+            }
+        }
+    }
+
+    /// Check that the physical address space's last page is granule-aligned.
+    #[kernel_test]
+    fn phys_addr_space_end_page_is_granule_aligned() {
+        let end_page: PageAddress<Physical> = phys_addr_space_end_page();
+
+        assert_eq!(end_page.into_inner() % KernelGranule::SIZE, 0);
+    }
+
+    /// Check `PageAddress` offsetting, including the overflow/underflow cases.
+    #[kernel_test]
+    fn page_address_checked_offset_works() {
+        let page = PageAddress::<Physical>::new(KernelGranule::SIZE);
+
+        assert_eq!(
+            page.checked_offset(1).unwrap().into_inner(),
+            2 * KernelGranule::SIZE
+        );
+        assert_eq!(page.checked_offset(-1).unwrap().into_inner(), 0);
+        assert!(page.checked_offset(-2).is_none());
+    }
 }
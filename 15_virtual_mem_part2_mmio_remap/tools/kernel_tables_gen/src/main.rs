@@ -0,0 +1,419 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// Copyright (c) 2018-2021 Andre Richter <andre.o.richter@gmail.com>
+
+//! Host-side tool that precomputes the kernel's static translation table entries from the linked
+//! kernel ELF and writes them back into the image.
+//!
+//! Run as a post-link build step, after `rustc` has produced the kernel ELF but before the raw
+//! binary is extracted for flashing. For each loadable segment, and for every page in it, it
+//! derives the same per-page `AttributeFields` that `bsp::raspberrypi::memory::mmu::
+//! kernel_map_binary()` builds by hand at runtime, and patches the packed descriptors into the
+//! `.kernel_tables` section so that `KERNEL_TABLES` is already valid by the time the MMU is
+//! enabled.
+
+use std::{convert::TryInto, env, fs, process};
+
+/// Matches `bsp::raspberrypi::memory::mmu::KernelGranule::SIZE`.
+const GRANULE_SIZE: u64 = 64 * 1024;
+
+const PT_LOAD: u32 = 1;
+const PF_X: u32 = 1;
+const PF_W: u32 = 2;
+
+/// Mirrors `memory::mmu::AccessPermissions`.
+#[derive(Clone, Copy)]
+enum AccessPermissions {
+    ReadOnly,
+    ReadWrite,
+}
+
+/// Mirrors `memory::mmu::AttributeFields`, as built by hand in `kernel_map_binary()`. Device
+/// memory never shows up in a statically linked segment, so `mem_attributes` is always
+/// `CacheableDRAM` here.
+#[derive(Clone, Copy)]
+struct AttributeFields {
+    acc_perms: AccessPermissions,
+    execute_never: bool,
+}
+
+/// A single statically known kernel segment, derived from one `PT_LOAD` program header.
+struct Segment {
+    virt_addr: u64,
+    phys_addr: u64,
+    size: u64,
+    attributes: AttributeFields,
+}
+
+fn main() {
+    let mut args = env::args().skip(1);
+    let elf_path = match args.next() {
+        Some(path) => path,
+        None => {
+            eprintln!("Usage: kernel_tables_gen <kernel.elf>");
+            process::exit(1);
+        }
+    };
+
+    let mut bytes = fs::read(&elf_path).expect("failed to read kernel ELF");
+    let segments = loadable_segments(&bytes);
+
+    for segment in &segments {
+        assert_eq!(
+            segment.virt_addr % GRANULE_SIZE,
+            0,
+            "segment at {:#x} is not granule-aligned",
+            segment.virt_addr
+        );
+        assert_eq!(
+            segment.phys_addr % GRANULE_SIZE,
+            0,
+            "segment at {:#x} has a physical address that is not granule-aligned",
+            segment.virt_addr
+        );
+        assert_eq!(
+            segment.size % GRANULE_SIZE,
+            0,
+            "segment at {:#x} has a size that is not a multiple of the granule",
+            segment.virt_addr
+        );
+    }
+
+    // One descriptor per page, not per segment: a segment spanning N pages needs N level-3
+    // entries, each with its own output address.
+    let packed: Vec<u64> = segments.iter().flat_map(page_descriptors).collect();
+    patch_kernel_tables_section(&mut bytes, &packed);
+
+    fs::write(&elf_path, bytes).expect("failed to write patched kernel ELF");
+}
+
+/// Parse the ELF64 program header table and turn each `PT_LOAD` entry into a `Segment`.
+fn loadable_segments(elf: &[u8]) -> Vec<Segment> {
+    let e_phoff = u64::from_le_bytes(elf[0x20..0x28].try_into().unwrap()) as usize;
+    let e_phentsize = u16::from_le_bytes(elf[0x36..0x38].try_into().unwrap()) as usize;
+    let e_phnum = u16::from_le_bytes(elf[0x38..0x3a].try_into().unwrap()) as usize;
+
+    let mut segments = Vec::new();
+    for i in 0..e_phnum {
+        let phdr = &elf[e_phoff + i * e_phentsize..];
+
+        let p_type = u32::from_le_bytes(phdr[0x00..0x04].try_into().unwrap());
+        if p_type != PT_LOAD {
+            continue;
+        }
+
+        let p_flags = u32::from_le_bytes(phdr[0x04..0x08].try_into().unwrap());
+        let p_vaddr = u64::from_le_bytes(phdr[0x10..0x18].try_into().unwrap());
+        let p_paddr = u64::from_le_bytes(phdr[0x18..0x20].try_into().unwrap());
+        let p_memsz = u64::from_le_bytes(phdr[0x28..0x30].try_into().unwrap());
+
+        segments.push(Segment {
+            virt_addr: p_vaddr,
+            phys_addr: p_paddr,
+            size: p_memsz,
+            attributes: AttributeFields {
+                acc_perms: if p_flags & PF_W != 0 {
+                    AccessPermissions::ReadWrite
+                } else {
+                    AccessPermissions::ReadOnly
+                },
+                execute_never: p_flags & PF_X == 0,
+            },
+        });
+    }
+
+    segments
+}
+
+/// Expand a segment into one packed descriptor per `KernelGranule::SIZE` page it spans, each
+/// carrying that page's own physical output address.
+fn page_descriptors(segment: &Segment) -> Vec<u64> {
+    let num_pages = segment.size / GRANULE_SIZE;
+
+    (0..num_pages)
+        .map(|i| pack_descriptor(segment.phys_addr + i * GRANULE_SIZE, segment.attributes))
+        .collect()
+}
+
+/// Pack one page's output physical address and permission/execute-never flags into a single
+/// descriptor, matching the encoding of the BSP's `encode_precomputed_page_descriptor`: bit 0 is
+/// `ReadWrite`, bit 1 is `execute_never`, and the granule-aligned high bits hold the page's
+/// physical output address (its low bits are zero, which leaves room for the flags).
+fn pack_descriptor(phys_page_addr: u64, attributes: AttributeFields) -> u64 {
+    let mut descriptor = phys_page_addr & !(GRANULE_SIZE - 1);
+
+    if matches!(attributes.acc_perms, AccessPermissions::ReadWrite) {
+        descriptor |= 0b01;
+    }
+    if attributes.execute_never {
+        descriptor |= 0b10;
+    }
+
+    descriptor
+}
+
+/// Overwrite the `.kernel_tables` section's contents in place with the packed descriptors.
+///
+/// Panics if the section doesn't exist, or if it is smaller than `packed.len() * 8` bytes, rather
+/// than silently spilling into whatever file content follows it.
+fn patch_kernel_tables_section(elf: &mut [u8], packed: &[u64]) {
+    let (offset, size) = find_section(elf, ".kernel_tables")
+        .expect(".kernel_tables section not found in kernel ELF");
+
+    let needed = packed.len() * 8;
+    assert!(
+        needed <= size,
+        ".kernel_tables section is {} bytes, but {} bytes of precomputed entries were generated",
+        size,
+        needed
+    );
+
+    for (i, descriptor) in packed.iter().enumerate() {
+        let start = offset + i * 8;
+        elf[start..start + 8].copy_from_slice(&descriptor.to_le_bytes());
+    }
+}
+
+/// Find a section's file offset and size by name, using the section header string table.
+fn find_section(elf: &[u8], name: &str) -> Option<(usize, usize)> {
+    let e_shoff = u64::from_le_bytes(elf[0x28..0x30].try_into().unwrap()) as usize;
+    let e_shentsize = u16::from_le_bytes(elf[0x3a..0x3c].try_into().unwrap()) as usize;
+    let e_shnum = u16::from_le_bytes(elf[0x3c..0x3e].try_into().unwrap()) as usize;
+    let e_shstrndx = u16::from_le_bytes(elf[0x3e..0x40].try_into().unwrap()) as usize;
+
+    let shstrtab_off = {
+        let shdr = &elf[e_shoff + e_shstrndx * e_shentsize..];
+        u64::from_le_bytes(shdr[0x18..0x20].try_into().unwrap()) as usize
+    };
+
+    for i in 0..e_shnum {
+        let shdr = &elf[e_shoff + i * e_shentsize..];
+
+        let sh_name = u32::from_le_bytes(shdr[0x00..0x04].try_into().unwrap()) as usize;
+        let sh_offset = u64::from_le_bytes(shdr[0x18..0x20].try_into().unwrap()) as usize;
+        let sh_size = u64::from_le_bytes(shdr[0x20..0x28].try_into().unwrap()) as usize;
+
+        let name_start = shstrtab_off + sh_name;
+        let name_end = elf[name_start..].iter().position(|&b| b == 0).unwrap() + name_start;
+        if &elf[name_start..name_end] == name.as_bytes() {
+            return Some((sh_offset, sh_size));
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Build a minimal ELF64 image with one `PT_LOAD` program header and a `.kernel_tables`
+    /// section, laid out precisely enough for `loadable_segments`/`find_section` to parse it.
+    ///
+    /// Segment: two pages, read-write, not executable (`p_flags == PF_W`).
+    fn synthetic_elf() -> Vec<u8> {
+        let mut elf = vec![0u8; 392];
+
+        // ELF header.
+        elf[0x20..0x28].copy_from_slice(&64u64.to_le_bytes()); // e_phoff
+        elf[0x28..0x30].copy_from_slice(&200u64.to_le_bytes()); // e_shoff
+        elf[0x36..0x38].copy_from_slice(&56u16.to_le_bytes()); // e_phentsize
+        elf[0x38..0x3a].copy_from_slice(&1u16.to_le_bytes()); // e_phnum
+        elf[0x3a..0x3c].copy_from_slice(&64u16.to_le_bytes()); // e_shentsize
+        elf[0x3c..0x3e].copy_from_slice(&3u16.to_le_bytes()); // e_shnum
+        elf[0x3e..0x40].copy_from_slice(&1u16.to_le_bytes()); // e_shstrndx
+
+        // Program header, at offset 64.
+        let phdr = &mut elf[64..64 + 56];
+        phdr[0x00..0x04].copy_from_slice(&PT_LOAD.to_le_bytes());
+        phdr[0x04..0x08].copy_from_slice(&PF_W.to_le_bytes());
+        phdr[0x10..0x18].copy_from_slice(&0x1000_0000u64.to_le_bytes()); // p_vaddr
+        phdr[0x18..0x20].copy_from_slice(&0x2000_0000u64.to_le_bytes()); // p_paddr
+        phdr[0x28..0x30].copy_from_slice(&(2 * GRANULE_SIZE).to_le_bytes()); // p_memsz
+
+        // Section header string table contents, at offset 120: an empty name, then
+        // ".kernel_tables".
+        elf[121..121 + 14].copy_from_slice(b".kernel_tables");
+
+        // Section headers, at offset 200: null, .shstrtab, .kernel_tables.
+        let shstrtab = &mut elf[264..264 + 64];
+        shstrtab[0x18..0x20].copy_from_slice(&120u64.to_le_bytes()); // sh_offset
+        shstrtab[0x20..0x28].copy_from_slice(&16u64.to_le_bytes()); // sh_size
+
+        let kernel_tables = &mut elf[328..328 + 64];
+        kernel_tables[0x00..0x04].copy_from_slice(&1u32.to_le_bytes()); // sh_name
+        kernel_tables[0x18..0x20].copy_from_slice(&136u64.to_le_bytes()); // sh_offset
+        kernel_tables[0x20..0x28].copy_from_slice(&64u64.to_le_bytes()); // sh_size
+
+        elf
+    }
+
+    #[test]
+    fn loadable_segments_reads_the_pt_load_entry() {
+        let segments = loadable_segments(&synthetic_elf());
+
+        assert_eq!(segments.len(), 1);
+        assert_eq!(segments[0].virt_addr, 0x1000_0000);
+        assert_eq!(segments[0].phys_addr, 0x2000_0000);
+        assert_eq!(segments[0].size, 2 * GRANULE_SIZE);
+        assert!(matches!(
+            segments[0].attributes.acc_perms,
+            AccessPermissions::ReadWrite
+        ));
+        assert!(segments[0].attributes.execute_never);
+    }
+
+    #[test]
+    fn find_section_locates_kernel_tables() {
+        let (offset, size) = find_section(&synthetic_elf(), ".kernel_tables").unwrap();
+
+        assert_eq!(offset, 136);
+        assert_eq!(size, 64);
+    }
+
+    #[test]
+    fn find_section_returns_none_for_missing_section() {
+        assert!(find_section(&synthetic_elf(), ".does_not_exist").is_none());
+    }
+
+    #[test]
+    fn pack_descriptor_encodes_access_permissions_and_execute_never() {
+        let read_write = AttributeFields {
+            acc_perms: AccessPermissions::ReadWrite,
+            execute_never: true,
+        };
+        let read_only = AttributeFields {
+            acc_perms: AccessPermissions::ReadOnly,
+            execute_never: false,
+        };
+
+        assert_eq!(pack_descriptor(0x4000_0000, read_write), 0x4000_0000 | 0b11);
+        assert_eq!(pack_descriptor(0x4000_0000, read_only), 0x4000_0000);
+    }
+
+    #[test]
+    fn pack_descriptor_masks_phys_addr_to_the_granule() {
+        let attributes = AttributeFields {
+            acc_perms: AccessPermissions::ReadOnly,
+            execute_never: true,
+        };
+
+        let descriptor = pack_descriptor(0x4000_0000 + 0x123, attributes);
+
+        assert_eq!(descriptor & !(GRANULE_SIZE - 1), 0x4000_0000);
+    }
+
+    #[test]
+    fn page_descriptors_emits_one_descriptor_per_page() {
+        let segment = Segment {
+            virt_addr: 0x1000_0000,
+            phys_addr: 0x2000_0000,
+            size: 2 * GRANULE_SIZE,
+            attributes: AttributeFields {
+                acc_perms: AccessPermissions::ReadWrite,
+                execute_never: true,
+            },
+        };
+
+        let descriptors = page_descriptors(&segment);
+
+        assert_eq!(descriptors.len(), 2);
+        assert_eq!(descriptors[0] & !(GRANULE_SIZE - 1), 0x2000_0000);
+        assert_eq!(
+            descriptors[1] & !(GRANULE_SIZE - 1),
+            0x2000_0000 + GRANULE_SIZE
+        );
+    }
+
+    #[test]
+    fn packed_descriptors_preserve_segment_and_page_order() {
+        // `main()` writes `segments.iter().flat_map(page_descriptors)` into `.kernel_tables`
+        // starting at its first byte, so the kernel side can only make sense of the result if
+        // descriptors come out in segment order, and pages within a segment in ascending order.
+        let segments = vec![
+            Segment {
+                virt_addr: 0x1000_0000,
+                phys_addr: 0x2000_0000,
+                size: GRANULE_SIZE,
+                attributes: AttributeFields {
+                    acc_perms: AccessPermissions::ReadOnly,
+                    execute_never: false,
+                },
+            },
+            Segment {
+                virt_addr: 0x1001_0000,
+                phys_addr: 0x3000_0000,
+                size: 2 * GRANULE_SIZE,
+                attributes: AttributeFields {
+                    acc_perms: AccessPermissions::ReadWrite,
+                    execute_never: true,
+                },
+            },
+        ];
+
+        let packed: Vec<u64> = segments.iter().flat_map(page_descriptors).collect();
+
+        assert_eq!(packed.len(), 3);
+        assert_eq!(packed[0] & !(GRANULE_SIZE - 1), 0x2000_0000);
+        assert_eq!(packed[1] & !(GRANULE_SIZE - 1), 0x3000_0000);
+        assert_eq!(packed[2] & !(GRANULE_SIZE - 1), 0x3000_0000 + GRANULE_SIZE);
+    }
+
+    #[test]
+    fn loadable_segments_skips_non_pt_load_entries_and_preserves_order() {
+        // Three program headers: PT_LOAD, PT_NOTE, PT_LOAD. `loadable_segments` must skip the
+        // PT_NOTE entry and return the two PT_LOAD segments in program-header order.
+        const PT_NOTE: u32 = 4;
+
+        let mut elf = vec![0u8; 56 * 3 + 64];
+        elf[0x20..0x28].copy_from_slice(&64u64.to_le_bytes()); // e_phoff
+        elf[0x36..0x38].copy_from_slice(&56u16.to_le_bytes()); // e_phentsize
+        elf[0x38..0x3a].copy_from_slice(&3u16.to_le_bytes()); // e_phnum
+
+        // Program headers, at offset 64, right after the ELF header.
+        let phdr0 = &mut elf[64..120];
+        phdr0[0x00..0x04].copy_from_slice(&PT_LOAD.to_le_bytes());
+        phdr0[0x04..0x08].copy_from_slice(&PF_W.to_le_bytes());
+        phdr0[0x10..0x18].copy_from_slice(&0x1000_0000u64.to_le_bytes());
+        phdr0[0x18..0x20].copy_from_slice(&0x2000_0000u64.to_le_bytes());
+        phdr0[0x28..0x30].copy_from_slice(&GRANULE_SIZE.to_le_bytes());
+
+        let phdr1 = &mut elf[120..176];
+        phdr1[0x00..0x04].copy_from_slice(&PT_NOTE.to_le_bytes());
+
+        let phdr2 = &mut elf[176..232];
+        phdr2[0x00..0x04].copy_from_slice(&PT_LOAD.to_le_bytes());
+        phdr2[0x04..0x08].copy_from_slice((PF_W | PF_X).to_le_bytes().as_slice());
+        phdr2[0x10..0x18].copy_from_slice(&0x1001_0000u64.to_le_bytes());
+        phdr2[0x18..0x20].copy_from_slice(&0x3000_0000u64.to_le_bytes());
+        phdr2[0x28..0x30].copy_from_slice(&GRANULE_SIZE.to_le_bytes());
+
+        let segments = loadable_segments(&elf);
+
+        assert_eq!(segments.len(), 2);
+        assert_eq!(segments[0].phys_addr, 0x2000_0000);
+        assert!(segments[0].attributes.execute_never);
+        assert_eq!(segments[1].phys_addr, 0x3000_0000);
+        assert!(!segments[1].attributes.execute_never);
+    }
+
+    #[test]
+    fn patch_kernel_tables_section_writes_packed_descriptors_at_the_section_offset() {
+        let mut elf = synthetic_elf();
+        let packed = [0x2000_0003u64, 0x2000_0000 + GRANULE_SIZE];
+
+        patch_kernel_tables_section(&mut elf, &packed);
+
+        assert_eq!(&elf[136..144], &packed[0].to_le_bytes());
+        assert_eq!(&elf[144..152], &packed[1].to_le_bytes());
+    }
+
+    #[test]
+    #[should_panic(expected = "bytes of precomputed entries were generated")]
+    fn patch_kernel_tables_section_rejects_entries_that_do_not_fit() {
+        let mut elf = synthetic_elf();
+        let too_many: Vec<u64> = vec![0; 9]; // section only holds 64 / 8 == 8 descriptors.
+
+        patch_kernel_tables_section(&mut elf, &too_many);
+    }
+}